@@ -1,5 +1,308 @@
-use tauri::{Manager, WindowEvent, menu::{MenuBuilder, MenuItemBuilder}, tray::{TrayIconBuilder, TrayIconEvent}};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tauri::{Emitter, Manager, WindowEvent, menu::{MenuBuilder, MenuItem, MenuItemBuilder}, tray::{TrayIconBuilder, TrayIconEvent}};
+use tauri_plugin_global_shortcut::{GlobalShortcutExt, Shortcut, ShortcutState};
 use tauri_plugin_notification::NotificationExt;
+use tauri_plugin_store::StoreExt;
+use tauri_plugin_updater::UpdaterExt;
+
+/// How long to wait after launch before silently checking for an update, so it doesn't
+/// compete with startup for network/CPU.
+const STARTUP_UPDATE_CHECK_DELAY: std::time::Duration = std::time::Duration::from_secs(10);
+
+/// Name of the on-disk key/value store backing persisted Luna settings.
+const SETTINGS_STORE: &str = "settings.json";
+
+/// Default accelerator used to summon/dismiss Luna from anywhere.
+const DEFAULT_TOGGLE_HOTKEY: &str = "CmdOrCtrl+Shift+Space";
+
+/// Persist `key` = `value` in the settings store, best-effort.
+fn save_setting(app: &tauri::AppHandle, key: &str, value: serde_json::Value) {
+    if let Ok(store) = app.store(SETTINGS_STORE) {
+        store.set(key, value);
+        let _ = store.save();
+    }
+}
+
+/// Read `key` back from the settings store, if it was previously persisted.
+fn load_setting(app: &tauri::AppHandle, key: &str) -> Option<serde_json::Value> {
+    app.store(SETTINGS_STORE).ok()?.get(key)
+}
+
+/// The currently-registered toggle hotkey, so it can be unregistered before re-registering.
+struct ToggleHotkeyState(Mutex<String>);
+
+/// Handle to the tray's "Show/Hide Luna" item, so both the window event handler and the
+/// menu/hotkey handlers can keep its label in sync with the main window's visibility.
+struct ToggleMenuItemState(MenuItem<tauri::Wry>);
+
+/// Update the tray menu's toggle item to say "Hide Luna" or "Show Luna".
+fn set_toggle_label(app: &tauri::AppHandle, main_visible: bool) {
+    if let Some(state) = app.try_state::<ToggleMenuItemState>() {
+        let label = if main_visible { "Hide Luna" } else { "Show Luna" };
+        state.0.set_text(label).ok();
+    }
+}
+
+/// Show and focus the `main` window if hidden, otherwise hide it to the tray.
+fn toggle_main_window(app: &tauri::AppHandle) {
+    if let Some(window) = app.get_webview_window("main") {
+        if window.is_visible().unwrap_or(false) {
+            window.hide().unwrap();
+            set_toggle_label(app, false);
+        } else {
+            reveal_main_window(app);
+        }
+    }
+}
+
+/// Show, unminimize, and focus the `main` window — used any time Luna is explicitly brought
+/// to the foreground (tray toggle, hotkey, relaunch). Always restores the dock icon, even if
+/// menubar-only mode is on, since the user asked to see Luna as a normal app.
+fn reveal_main_window(app: &tauri::AppHandle) {
+    if let Some(window) = app.get_webview_window("main") {
+        window.show().unwrap();
+        window.unminimize().unwrap();
+        window.set_focus().unwrap();
+        set_toggle_label(app, true);
+
+        #[cfg(target_os = "macos")]
+        app.set_activation_policy(tauri::ActivationPolicy::Regular).ok();
+    }
+}
+
+/// Register `accelerator` as the global toggle hotkey. The new accelerator is registered
+/// before the old one is dropped, so if it's already taken by another application the user
+/// keeps their previously-working hotkey instead of losing it.
+fn register_toggle_hotkey(app: &tauri::AppHandle, accelerator: &str) -> Result<(), String> {
+    let shortcut: Shortcut = accelerator.parse().map_err(|e| format!("{e}"))?;
+
+    let state = app.state::<ToggleHotkeyState>();
+    let mut current = state.0.lock().unwrap();
+
+    app.global_shortcut()
+        .on_shortcut(shortcut, |app, _shortcut, event| {
+            if event.state() == ShortcutState::Pressed {
+                toggle_main_window(app);
+            }
+        })
+        .map_err(|e| e.to_string())?;
+
+    // Only safe to drop the previous binding now that the new one is confirmed registered.
+    if *current != accelerator {
+        if let Ok(old_shortcut) = current.parse::<Shortcut>() {
+            let _ = app.global_shortcut().unregister(old_shortcut);
+        }
+    }
+
+    *current = accelerator.to_string();
+    Ok(())
+}
+
+#[tauri::command]
+fn set_toggle_hotkey(app: tauri::AppHandle, accelerator: String) -> Result<(), String> {
+    register_toggle_hotkey(&app, &accelerator)?;
+    save_setting(&app, "toggle_hotkey", serde_json::json!(accelerator));
+    Ok(())
+}
+
+#[tauri::command]
+fn get_toggle_hotkey(app: tauri::AppHandle) -> String {
+    app.state::<ToggleHotkeyState>().0.lock().unwrap().clone()
+}
+
+/// How often the idle-timeout background task checks whether the main window should be
+/// auto-hidden.
+const IDLE_CHECK_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Tracks activity for the auto-hide idle timeout: when the user last interacted with Luna,
+/// and how long the main window may sit visible-but-unfocused before it's hidden to the tray.
+struct AutoHideState {
+    last_activity: Mutex<Instant>,
+    timeout: Mutex<Option<Duration>>,
+}
+
+/// Record that the user just interacted with Luna, resetting the idle clock.
+fn record_activity(app: &tauri::AppHandle) {
+    if let Some(state) = app.try_state::<AutoHideState>() {
+        *state.last_activity.lock().unwrap() = Instant::now();
+    }
+}
+
+#[tauri::command]
+fn notify_activity(app: tauri::AppHandle) {
+    record_activity(&app);
+}
+
+#[tauri::command]
+fn set_auto_hide(app: tauri::AppHandle, seconds: Option<u32>) {
+    let state = app.state::<AutoHideState>();
+    *state.timeout.lock().unwrap() = seconds.map(|s| Duration::from_secs(s as u64));
+    *state.last_activity.lock().unwrap() = Instant::now();
+    save_setting(&app, "auto_hide_seconds", serde_json::json!(seconds));
+}
+
+/// Background loop: while the main window is visible, unfocused, and idle past the
+/// configured timeout, hide it to the tray — the same path `CloseRequested` uses.
+async fn run_auto_hide_loop(app: tauri::AppHandle) {
+    loop {
+        tokio::time::sleep(IDLE_CHECK_INTERVAL).await;
+
+        let state = app.state::<AutoHideState>();
+        let Some(timeout) = *state.timeout.lock().unwrap() else {
+            continue;
+        };
+
+        let Some(window) = app.get_webview_window("main") else {
+            continue;
+        };
+        if !window.is_visible().unwrap_or(false) {
+            continue;
+        }
+        if window.is_focused().unwrap_or(false) {
+            // Being focused counts as ongoing activity, so the idle clock only starts
+            // once the window loses focus rather than from whenever it last gained it.
+            *state.last_activity.lock().unwrap() = Instant::now();
+            continue;
+        }
+
+        let idle_for = state.last_activity.lock().unwrap().elapsed();
+        if idle_for >= timeout {
+            window.hide().unwrap();
+            set_toggle_label(&app, false);
+        }
+    }
+}
+
+/// Switch between a normal dock presence and tray-only "menubar" mode on macOS. Elsewhere
+/// the choice is only persisted, since only macOS has a dock icon to suppress.
+#[tauri::command]
+fn set_menubar_mode(app: tauri::AppHandle, enabled: bool) -> Result<(), String> {
+    #[cfg(target_os = "macos")]
+    {
+        let policy = if enabled {
+            tauri::ActivationPolicy::Accessory
+        } else {
+            tauri::ActivationPolicy::Regular
+        };
+        app.set_activation_policy(policy).map_err(|e| e.to_string())?;
+    }
+    #[cfg(not(target_os = "macos"))]
+    let _ = &app;
+
+    save_setting(&app, "menubar_mode", serde_json::json!(enabled));
+    Ok(())
+}
+
+/// How long after a blur-triggered panel hide a tray click is treated as "the click that
+/// dismissed it" rather than a request to reopen it.
+const PANEL_DISMISS_DEBOUNCE: Duration = Duration::from_millis(250);
+
+/// When the panel was last hidden by losing focus, so a tray left-click immediately after
+/// (the same click that caused the blur) doesn't race the hide and reopen the popover.
+struct PanelState {
+    last_blur_hidden_at: Mutex<Option<Instant>>,
+}
+
+/// Whether a tray left-click opens the glance panel (the default) or falls back to the
+/// original behavior of toggling the main window. Persisted so users who prefer the main
+/// window as their primary tray gesture can opt out of the panel.
+const DEFAULT_TRAY_CLICK_OPENS_PANEL: bool = true;
+
+struct TrayClickModeState(Mutex<bool>);
+
+#[tauri::command]
+fn set_tray_click_opens_panel(app: tauri::AppHandle, enabled: bool) {
+    *app.state::<TrayClickModeState>().0.lock().unwrap() = enabled;
+    save_setting(&app, "tray_click_opens_panel", serde_json::json!(enabled));
+}
+
+/// Find the monitor that contains `point`. Returns `None` if the point doesn't land on any
+/// known monitor (e.g. a stale coordinate during a display change); `position_panel` then
+/// falls back to the primary monitor.
+fn monitor_at(
+    window: &tauri::WebviewWindow,
+    point: tauri::PhysicalPosition<f64>,
+) -> Option<tauri::window::Monitor> {
+    window.available_monitors().ok()?.into_iter().find(|monitor| {
+        let pos = monitor.position();
+        let size = monitor.size();
+        point.x >= pos.x as f64
+            && point.x < pos.x as f64 + size.width as f64
+            && point.y >= pos.y as f64
+            && point.y < pos.y as f64 + size.height as f64
+    })
+}
+
+/// Position the `panel` webview next to a tray click, clamped to the work area of the
+/// monitor the click happened on (not necessarily the primary monitor).
+///
+/// On Windows/Linux the tray lives in a taskbar that can be at the top or bottom of the
+/// screen, so we place the panel above the click point if it's in the lower half of the
+/// monitor and below it otherwise. On macOS the menubar is always at the top, so the panel
+/// always opens below the click point.
+fn position_panel(panel: &tauri::WebviewWindow, click_position: tauri::PhysicalPosition<f64>) {
+    let monitor = monitor_at(panel, click_position)
+        .or_else(|| panel.primary_monitor().ok().flatten());
+    let Some(monitor) = monitor else {
+        return;
+    };
+
+    let work_area = monitor.work_area();
+    let panel_size = panel
+        .outer_size()
+        .unwrap_or(tauri::PhysicalSize::new(360, 480));
+    let (panel_width, panel_height) = (panel_size.width as f64, panel_size.height as f64);
+
+    let y = if cfg!(target_os = "macos") {
+        click_position.y + 8.0
+    } else if click_position.y > work_area.position.y as f64 + (work_area.size.height as f64 / 2.0) {
+        click_position.y - panel_height - 8.0
+    } else {
+        click_position.y + 8.0
+    };
+
+    let min_x = work_area.position.x as f64;
+    let max_x = min_x + work_area.size.width as f64 - panel_width;
+    let x = (click_position.x - panel_width / 2.0).clamp(min_x, max_x.max(min_x));
+
+    let min_y = work_area.position.y as f64;
+    let max_y = min_y + work_area.size.height as f64 - panel_height;
+    let y = y.clamp(min_y, max_y.max(min_y));
+
+    panel
+        .set_position(tauri::Position::Physical(tauri::PhysicalPosition {
+            x: x as i32,
+            y: y as i32,
+        }))
+        .ok();
+}
+
+/// Check the update endpoint and, if a newer version is available, notify the user and let
+/// the frontend know via a `"update-available"` event so it can offer install-and-restart.
+#[tauri::command]
+async fn check_for_updates(app: tauri::AppHandle) -> Result<bool, String> {
+    let update = app
+        .updater()
+        .map_err(|e| e.to_string())?
+        .check()
+        .await
+        .map_err(|e| e.to_string())?;
+
+    match update {
+        Some(update) => {
+            app.notification()
+                .builder()
+                .title("Luna update ready")
+                .body(format!("Version {} is available.", update.version))
+                .show()
+                .ok();
+            let _ = app.emit("update-available", &update.version);
+            Ok(true)
+        }
+        None => Ok(false),
+    }
+}
 
 #[tauri::command]
 async fn show_notification(app: tauri::AppHandle, title: String, body: String) -> Result<(), String> {
@@ -15,18 +318,62 @@ async fn show_notification(app: tauri::AppHandle, title: String, body: String) -
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
     tauri::Builder::default()
+        .plugin(tauri_plugin_single_instance::init(|app, argv, _cwd| {
+            // Luna minimizes to tray on close rather than quitting, so a relaunch should
+            // bring the existing instance forward instead of spawning a duplicate process.
+            reveal_main_window(app);
+            let _ = app.emit("single-instance", argv);
+        }))
         .plugin(tauri_plugin_notification::init())
         .plugin(tauri_plugin_window_state::Builder::default().build())
+        .plugin(tauri_plugin_global_shortcut::Builder::new().build())
+        .plugin(tauri_plugin_store::Builder::default().build())
+        .plugin(tauri_plugin_updater::Builder::new().build())
+        .manage(ToggleHotkeyState(Mutex::new(DEFAULT_TOGGLE_HOTKEY.to_string())))
+        .manage(AutoHideState {
+            last_activity: Mutex::new(Instant::now()),
+            timeout: Mutex::new(None),
+        })
+        .manage(PanelState {
+            last_blur_hidden_at: Mutex::new(None),
+        })
+        .manage(TrayClickModeState(Mutex::new(DEFAULT_TRAY_CLICK_OPENS_PANEL)))
         .on_window_event(|window, event| {
             if let WindowEvent::CloseRequested { api, .. } = event {
                 // Minimize to system tray instead of closing (only for main window)
                 if window.label() == "main" {
                     window.hide().unwrap();
+                    set_toggle_label(&window.app_handle(), false);
                     api.prevent_close();
                 }
             }
+
+            // The panel is a focus-losing popover, like a native menubar dropdown.
+            if window.label() == "panel" {
+                if let WindowEvent::Focused(false) = event {
+                    window.hide().unwrap();
+                    if let Some(state) = window.app_handle().try_state::<PanelState>() {
+                        *state.last_blur_hidden_at.lock().unwrap() = Some(Instant::now());
+                    }
+                }
+            }
+
+            if window.label() == "main" {
+                if let WindowEvent::Focused(true) = event {
+                    record_activity(&window.app_handle());
+                }
+            }
         })
-        .invoke_handler(tauri::generate_handler![show_notification])
+        .invoke_handler(tauri::generate_handler![
+            show_notification,
+            set_toggle_hotkey,
+            get_toggle_hotkey,
+            set_menubar_mode,
+            check_for_updates,
+            notify_activity,
+            set_auto_hide,
+            set_tray_click_opens_panel
+        ])
         .setup(|app| {
             if cfg!(debug_assertions) {
                 app.handle().plugin(
@@ -36,13 +383,48 @@ pub fn run() {
                 )?;
             }
 
+            // Restore the persisted toggle hotkey, falling back to the default.
+            let saved_hotkey = load_setting(app.handle(), "toggle_hotkey")
+                .and_then(|v| v.as_str().map(String::from))
+                .unwrap_or_else(|| DEFAULT_TOGGLE_HOTKEY.to_string());
+            if let Err(e) = register_toggle_hotkey(app.handle(), &saved_hotkey) {
+                log::warn!("failed to register toggle hotkey '{saved_hotkey}': {e}");
+            }
+
+            // Restore persisted menubar-only mode (macOS dock icon suppression).
+            #[cfg(target_os = "macos")]
+            {
+                let menubar_mode = load_setting(app.handle(), "menubar_mode")
+                    .and_then(|v| v.as_bool())
+                    .unwrap_or(false);
+                if menubar_mode {
+                    app.set_activation_policy(tauri::ActivationPolicy::Accessory)?;
+                }
+            }
+
+            // Restore the persisted tray-click mode.
+            if let Some(opens_panel) = load_setting(app.handle(), "tray_click_opens_panel")
+                .and_then(|v| v.as_bool())
+            {
+                *app.state::<TrayClickModeState>().0.lock().unwrap() = opens_panel;
+            }
+
             // Build System Tray Menu
             let toggle = MenuItemBuilder::with_id("toggle", "Show/Hide Luna").build(app)?;
             let settings = MenuItemBuilder::with_id("settings", "Settings").build(app)?;
+            let update = MenuItemBuilder::with_id("update", "Check for updates…").build(app)?;
             let quit = MenuItemBuilder::with_id("quit", "Quit").build(app)?;
+
+            app.manage(ToggleMenuItemState(toggle.clone()));
+            set_toggle_label(
+                app.handle(),
+                app.get_webview_window("main")
+                    .map(|w| w.is_visible().unwrap_or(false))
+                    .unwrap_or(false),
+            );
             
             let menu = MenuBuilder::new(app)
-                .items(&[&toggle, &settings, &quit])
+                .items(&[&toggle, &settings, &update, &quit])
                 .build()?;
             
             // Create System Tray Icon
@@ -53,13 +435,7 @@ pub fn run() {
                 .on_menu_event(|app, event| {
                     match event.id().as_ref() {
                         "toggle" => {
-                            if let Some(window) = app.get_webview_window("main") {
-                                if window.is_visible().unwrap_or(false) {
-                                    window.hide().unwrap();
-                                } else {
-                                    window.show().unwrap();
-                                }
-                            }
+                            toggle_main_window(app);
                         }
                         "settings" => {
                             if let Some(settings_window) = app.get_webview_window("settings") {
@@ -67,6 +443,14 @@ pub fn run() {
                                 settings_window.set_focus().unwrap();
                             }
                         }
+                        "update" => {
+                            let app = app.clone();
+                            tauri::async_runtime::spawn(async move {
+                                if let Err(e) = check_for_updates(app).await {
+                                    log::warn!("update check failed: {e}");
+                                }
+                            });
+                        }
                         "quit" => {
                             std::process::exit(0);
                         }
@@ -74,15 +458,39 @@ pub fn run() {
                     }
                 })
                 .on_tray_icon_event(|tray, event| {
-                    // Left click on tray icon = toggle main window visibility
-                    if let TrayIconEvent::Click { button, .. } = event {
+                    // Left click on tray icon: by default toggles the notification panel
+                    // popover (a small, borderless webview defined in `tauri.conf.json`).
+                    // Users who prefer the original gesture can opt back into toggling the
+                    // main window via `set_tray_click_opens_panel(false)`.
+                    if let TrayIconEvent::Click { button, position, .. } = event {
                         if button == tauri::tray::MouseButton::Left {
                             let app = tray.app_handle();
-                            if let Some(window) = app.get_webview_window("main") {
-                                if window.is_visible().unwrap_or(false) {
-                                    window.hide().unwrap();
+                            let opens_panel = app
+                                .try_state::<TrayClickModeState>()
+                                .map(|s| *s.0.lock().unwrap())
+                                .unwrap_or(DEFAULT_TRAY_CLICK_OPENS_PANEL);
+
+                            if !opens_panel {
+                                toggle_main_window(app);
+                                return;
+                            }
+
+                            if let Some(panel) = app.get_webview_window("panel") {
+                                if panel.is_visible().unwrap_or(false) {
+                                    panel.hide().unwrap();
                                 } else {
-                                    window.show().unwrap();
+                                    // A blur-triggered hide from this very click already
+                                    // dismissed the panel; don't immediately reopen it.
+                                    let just_dismissed = app
+                                        .try_state::<PanelState>()
+                                        .and_then(|s| *s.last_blur_hidden_at.lock().unwrap())
+                                        .is_some_and(|t| t.elapsed() < PANEL_DISMISS_DEBOUNCE);
+
+                                    if !just_dismissed {
+                                        position_panel(&panel, position);
+                                        panel.show().unwrap();
+                                        panel.set_focus().unwrap();
+                                    }
                                 }
                             }
                         }
@@ -108,6 +516,25 @@ pub fn run() {
                 })).ok();
             }
 
+            // Restore the persisted idle auto-hide timeout, if one was configured.
+            if let Some(seconds) = load_setting(app.handle(), "auto_hide_seconds")
+                .and_then(|v| serde_json::from_value::<Option<u32>>(v).ok())
+                .flatten()
+            {
+                *app.state::<AutoHideState>().timeout.lock().unwrap() =
+                    Some(Duration::from_secs(seconds as u64));
+            }
+            tauri::async_runtime::spawn(run_auto_hide_loop(app.handle().clone()));
+
+            // Silently check for an update shortly after launch.
+            let update_check_handle = app.handle().clone();
+            tauri::async_runtime::spawn(async move {
+                tokio::time::sleep(STARTUP_UPDATE_CHECK_DELAY).await;
+                if let Err(e) = check_for_updates(update_check_handle).await {
+                    log::warn!("startup update check failed: {e}");
+                }
+            });
+
             Ok(())
         })
         .run(tauri::generate_context!())